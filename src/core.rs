@@ -2,11 +2,14 @@ use std::collections::HashSet;
 
 use anyhow::{ensure, Context, Result};
 use clap::ArgMatches;
+use clap_complete::{generate, Shell};
+use regex::Regex;
 
 use crate::action;
 use crate::backend::{Backends, ToDoPerBackend};
+use crate::cli::build_cli;
 use crate::cmd::run_edit_command;
-use crate::ui::get_user_confirmation;
+use crate::ui::{get_user_confirmation, status, Spinner};
 use crate::Group;
 
 pub struct Pacdef {
@@ -14,6 +17,16 @@ pub struct Pacdef {
     groups: HashSet<Group>,
 }
 
+// Suppresses the default panic hook so a backend panic recovered via
+// `JoinHandle::join` doesn't also dump a trace to stderr.
+fn with_quiet_panic_hook<T>(f: impl FnOnce() -> T) -> T {
+    let previous_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(|_| {}));
+    let result = f();
+    std::panic::set_hook(previous_hook);
+    result
+}
+
 impl Pacdef {
     #[must_use]
     pub fn new(args: ArgMatches, groups: HashSet<Group>) -> Self {
@@ -24,10 +37,19 @@ impl Pacdef {
     pub fn run_action_from_arg(self) -> Result<()> {
         match self.args.subcommand() {
             Some((action::CLEAN, _)) => Ok(self.clean_packages()),
+            Some((action::COMPLETIONS, args)) => self
+                .generate_completions(args)
+                .context("generating completions"),
             Some((action::EDIT, groups)) => {
                 self.edit_group_files(groups).context("editing group files")
             }
             Some((action::GROUPS, _)) => Ok(self.show_groups()),
+            Some((action::SEARCH, args)) => self
+                .search_packages(
+                    args.get_one::<String>("regex")
+                        .context("getting regex from args")?,
+                )
+                .context("searching packages"),
             Some((action::SYNC, _)) => Ok(self.install_packages()),
             Some((action::UNMANAGED, _)) => Ok(self.show_unmanaged_packages()),
             Some((action::VERSION, _)) => Ok(self.show_version()),
@@ -38,16 +60,45 @@ impl Pacdef {
 
     fn get_missing_packages(&self) -> ToDoPerBackend {
         let mut to_install = ToDoPerBackend::new();
+        let mut spinner = Spinner::new("loading backends");
 
-        for mut backend in Backends::iter() {
-            backend.load(&self.groups);
+        with_quiet_panic_hook(|| {
+            std::thread::scope(|scope| {
+                let handles: Vec<_> = Backends::iter()
+                    .map(|mut backend| {
+                        let section = backend.get_section().to_string();
+                        let handle = scope.spawn(move || {
+                            backend.load(&self.groups);
+                            let diff = backend.get_missing_packages_sorted();
+                            (backend, diff)
+                        });
+                        (section, handle)
+                    })
+                    .collect();
 
-            match backend.get_missing_packages_sorted() {
-                Ok(diff) => to_install.push((backend, diff)),
-                Err(e) => println!("WARNING: skipping backend '{}': {e}", backend.get_section()),
-            };
-        }
+                for (section, handle) in handles {
+                    match handle.join() {
+                        Ok((backend, diff)) => {
+                            spinner.update(backend.get_section());
+                            match diff {
+                                Ok(diff) => to_install.push((backend, diff)),
+                                Err(e) => {
+                                    spinner.warn(&format!(
+                                        "WARNING: skipping backend '{section}': {e}"
+                                    ));
+                                }
+                            };
+                        }
+                        Err(_) => {
+                            spinner
+                                .warn(&format!("WARNING: skipping backend '{section}': panicked"));
+                        }
+                    }
+                }
+            });
+        });
 
+        spinner.clear();
         to_install
     }
 
@@ -55,19 +106,23 @@ impl Pacdef {
         let to_install = self.get_missing_packages();
 
         if to_install.nothing_to_do_for_all_backends() {
-            println!("nothing to do");
+            status("nothing to do");
             return;
         }
 
         to_install.show();
 
-        if !get_user_confirmation() {
+        if !self.noconfirm() && !get_user_confirmation() {
             return;
         };
 
         to_install.install_missing_packages();
     }
 
+    fn noconfirm(&self) -> bool {
+        self.args.get_flag("noconfirm")
+    }
+
     fn edit_group_files(&self, groups: &ArgMatches) -> Result<()> {
         let group_dir = crate::path::get_pacdef_group_dir()?;
 
@@ -97,6 +152,18 @@ impl Pacdef {
         Ok(())
     }
 
+    fn generate_completions(&self, args: &ArgMatches) -> Result<()> {
+        let shell = *args
+            .get_one::<Shell>("shell")
+            .context("getting shell from args")?;
+
+        let mut cmd = build_cli();
+        let name = cmd.get_name().to_string();
+        generate(shell, &mut cmd, name, &mut std::io::stdout());
+
+        Ok(())
+    }
+
     fn show_version(self) {
         println!("pacdef, version: {}", env!("CARGO_PKG_VERSION"));
     }
@@ -108,24 +175,54 @@ impl Pacdef {
             if packages.is_empty() {
                 continue;
             }
-            println!("{}", backend.get_section());
+            status(backend.get_section());
             for package in packages {
-                println!("  {package}");
+                status(&format!("  {package}"));
             }
         }
     }
 
     fn get_unmanaged_packages(self) -> ToDoPerBackend {
         let mut result = ToDoPerBackend::new();
+        let mut spinner = Spinner::new("loading backends");
 
-        for mut backend in Backends::iter() {
-            backend.load(&self.groups);
+        with_quiet_panic_hook(|| {
+            std::thread::scope(|scope| {
+                let handles: Vec<_> = Backends::iter()
+                    .map(|mut backend| {
+                        let section = backend.get_section().to_string();
+                        let handle = scope.spawn(move || {
+                            backend.load(&self.groups);
+                            let diff = backend.get_unmanaged_packages_sorted();
+                            (backend, diff)
+                        });
+                        (section, handle)
+                    })
+                    .collect();
 
-            match backend.get_unmanaged_packages_sorted() {
-                Ok(unmanaged) => result.push((backend, unmanaged)),
-                Err(e) => println!("WARNING: skipping backend '{}': {e}", backend.get_section()),
-            };
-        }
+                for (section, handle) in handles {
+                    match handle.join() {
+                        Ok((backend, diff)) => {
+                            spinner.update(backend.get_section());
+                            match diff {
+                                Ok(unmanaged) => result.push((backend, unmanaged)),
+                                Err(e) => {
+                                    spinner.warn(&format!(
+                                        "WARNING: skipping backend '{section}': {e}"
+                                    ));
+                                }
+                            };
+                        }
+                        Err(_) => {
+                            spinner
+                                .warn(&format!("WARNING: skipping backend '{section}': panicked"));
+                        }
+                    }
+                }
+            });
+        });
+
+        spinner.clear();
         result
     }
 
@@ -133,30 +230,52 @@ impl Pacdef {
         let mut vec: Vec<_> = self.groups.iter().collect();
         vec.sort_unstable();
         for g in vec {
-            println!("{}", g.name);
+            status(&g.name);
         }
     }
 
+    fn search_packages(&self, pattern: &str) -> Result<()> {
+        let re = Regex::new(pattern).context("compiling regex")?;
+
+        let mut groups: Vec<_> = self.groups.iter().collect();
+        groups.sort_unstable();
+
+        for group in groups {
+            for (section, packages) in &group.packages {
+                for package in packages {
+                    if re.is_match(package) {
+                        status(&format!("{}: [{section}] {package}", group.name));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     fn clean_packages(self) {
+        let noconfirm = self.noconfirm();
         let to_remove = self.get_unmanaged_packages();
         if to_remove.is_empty() {
-            println!("nothing to do");
+            status("nothing to do");
             return;
         }
 
-        println!("Would remove the following packages and their dependencies:");
+        status("Would remove the following packages and their dependencies:");
         for (backend, packages) in to_remove.iter() {
             if packages.is_empty() {
                 continue;
             }
 
-            println!("  {}", backend.get_section());
-            for package in packages {
-                println!("    {package}");
+            let closure = backend.get_removal_closure(packages);
+
+            status(&format!("  {}", backend.get_section()));
+            for package in &closure {
+                status(&format!("    {package}"));
             }
         }
 
-        if !get_user_confirmation() {
+        if !noconfirm && !get_user_confirmation() {
             return;
         };
 